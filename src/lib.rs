@@ -33,38 +33,28 @@ use ::failure::Fail;
 
 #[derive(Fail, Debug)]
 pub enum Error {
-    #[fail(display = "There is not enough ID now")]
-    TimeOverflow,
     #[fail(display = "SystemTime before UNIX EPOCH!")]
     SystemTimeException,
     #[fail(display = "Too many threads")]
     WorkerIDOverflow,
     #[fail(display = "SystemTime before EPOCH!")]
     EpochException,
+    #[fail(display = "Invalid base32 string or id buffer length")]
+    InvalidLength,
+    #[fail(display = "Invalid base32 character")]
+    InvalidChar,
+    #[fail(display = "System clock moved backwards")]
+    ClockRollback,
 }
 
-use std::cell::RefCell;
+use std::cell::Cell;
+use std::convert::TryInto;
 use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
 
 const UUID_TICKS_BETWEEN_EPOCHS: u64 = 0x01B2_1DD2_1381_4000;
 const TIMESTAMP42SHIFT: u8 = 13;
 
-static mut COUNTER: AtomicUsize = AtomicUsize::new(0);
-
-thread_local! {
-    static WORKER_ID: [u8;2] = {
-        unsafe{
-            let id = COUNTER.fetch_add(1, Ordering::SeqCst);
-            if id > u16::max_value() as usize {
-                panic!("too many threads")
-            };
-            (id as u16).to_be_bytes()
-        }
-    };
-    static SEQ: RefCell<u16> = RefCell::new(0);
-    static TIMESTAMP: RefCell<u64> = RefCell::new(now().unwrap());
-}
-
 //100ns since unix_epoch;
 fn now() -> Result<u64, Error> {
     use std::time::{SystemTime, UNIX_EPOCH};
@@ -74,56 +64,49 @@ fn now() -> Result<u64, Error> {
     Ok((time.as_nanos() / 100) as u64 + UUID_TICKS_BETWEEN_EPOCHS)
 }
 
-fn worker_id() -> [u8; 2] {
-    WORKER_ID.with(|f| *f)
-}
-
-fn time_inc(min_interval: u16) -> Result<u64, Error> {
-    TIMESTAMP.with(|t| {
-        let mut time = t.borrow_mut();
-        if cfg!(test) && (*time) >= now()? {
-            return Err(Error::TimeOverflow);
-        }
-        *time += u64::from(min_interval);
-        Ok(*time)
-    })
+/// Reads the wall clock, guarding against it having moved backwards of
+/// `last`.
+///
+/// Any backward jump (NTP step, VM migration) is reported as
+/// `Error::ClockRollback` rather than minting an id that could collide
+/// with or precede one already handed out.
+fn next_tick(last: u64) -> Result<u64, Error> {
+    let current = now()?;
+    if current < last {
+        return Err(Error::ClockRollback);
+    }
+    Ok(current)
 }
 
-fn next(min_interval: u16) -> Result<(u64, u16), Error> {
-    SEQ.with(|s| {
-        let mut seq = s.borrow_mut();
-        if *seq < ((1 << 14) - 1) {
-            *seq += 1;
-            Ok((timestamp(), *seq))
-        } else {
-            let t = time_inc(min_interval)?;
-            *seq = 0;
-            Ok((t, 0))
+/// Busy-spins until the wall clock has advanced at least `min_interval`
+/// ticks past `last`, used when the 14-bit sequence for the current tick
+/// is exhausted and a new tick is required.
+fn wait_for_tick_after(last: u64, min_interval: u16) -> Result<u64, Error> {
+    loop {
+        let current = next_tick(last)?;
+        if current >= last + u64::from(min_interval) {
+            return Ok(current);
         }
-    })
-}
-
-fn timestamp() -> u64 {
-    TIMESTAMP.with(|t| *t.borrow())
+    }
 }
 
-#[cfg(test)]
-fn seq() -> u16 {
-    SEQ.with(|s| *s.borrow())
+/// Shifts a raw timestamp (100ns ticks since the UUID epoch) into the
+/// 42-bit, `TIMESTAMP42SHIFT`-resolution form that `next_short_96` and
+/// `next_short_64` pack into their id.
+fn shift_timestamp(t: u64, epoch: u64) -> Result<u64, Error> {
+    Ok((t
+        .checked_sub(UUID_TICKS_BETWEEN_EPOCHS)
+        .ok_or(Error::EpochException)?
+        .checked_sub(epoch)
+        .ok_or(Error::EpochException)?)
+        >> TIMESTAMP42SHIFT)
 }
 
-///
-/// for compatible UUID
-///
-/// 16 bit worker id and 24 bit machine_id
-///
-pub fn next_short_128(machine_id: [u8; 4]) -> Result<[u8; 16], Error> {
-    let (t, s) = next(1)?;
-    let w = worker_id();
+fn encode_128(t: u64, s: u16, w: [u8; 2], machine_id: [u8; 4]) -> [u8; 16] {
     let time_low = ((t & 0xFFFF_FFFF) as u32).to_be_bytes();
     let time_mid = (((t >> 32) & 0xFFFF) as u16).to_be_bytes();
     let time_high_and_version = ((((t >> 48) & 0x0FFF) as u16) | (1 << 12)).to_be_bytes();
-    Ok([
+    [
         time_low[0],
         time_low[1],
         time_low[2],
@@ -140,7 +123,307 @@ pub fn next_short_128(machine_id: [u8; 4]) -> Result<[u8; 16], Error> {
         machine_id[1],
         machine_id[2],
         machine_id[3],
-    ])
+    ]
+}
+
+fn encode_96(t: u64, s: u16, w: [u8; 2], machine_id: [u8; 3]) -> [u8; 12] {
+    let t_hi = (t >> 2).to_be_bytes();
+    let [t_low_and_s_hi, s_low] = (((t as u16) << 14) | s).to_be_bytes();
+    [
+        t_hi[3],
+        t_hi[4],
+        t_hi[5],
+        t_hi[6],
+        t_hi[7],
+        t_low_and_s_hi,
+        s_low,
+        w[0],
+        w[1],
+        machine_id[0],
+        machine_id[1],
+        machine_id[2],
+    ]
+}
+
+fn encode_64(t: u64, s: u16, w: u8) -> [u8; 8] {
+    let t_hi = (t >> 2).to_be_bytes();
+    let [t_low_and_s_hi, s_low] = (((t as u16) << 14) | s).to_be_bytes();
+    [
+        t_hi[3],
+        t_hi[4],
+        t_hi[5],
+        t_hi[6],
+        t_hi[7],
+        t_low_and_s_hi,
+        s_low,
+        w,
+    ]
+}
+
+/// Writes big-endian unsigned integers into a byte slice, advancing an
+/// internal offset as it goes. The write-side counterpart of `Decoder`.
+struct Encoder<'a> {
+    buf: &'a mut [u8],
+    offset: usize,
+}
+
+impl<'a> Encoder<'a> {
+    fn new(buf: &'a mut [u8]) -> Self {
+        Encoder { buf, offset: 0 }
+    }
+
+    fn encode_uint(&mut self, value: u64, n_bytes: usize) {
+        for i in 0..n_bytes {
+            let shift = 8 * (n_bytes - 1 - i);
+            self.buf[self.offset + i] = ((value >> shift) & 0xFF) as u8;
+        }
+        self.offset += n_bytes;
+    }
+}
+
+fn write_128(out: &mut [u8], t: u64, s: u16, w: [u8; 2], machine_id: [u8; 4]) {
+    let mut e = Encoder::new(out);
+    e.encode_uint(t & 0xFFFF_FFFF, 4);
+    e.encode_uint((t >> 32) & 0xFFFF, 2);
+    e.encode_uint(((t >> 48) & 0x0FFF) | (1 << 12), 2);
+    e.encode_uint(u64::from((((s & 0x3F00) >> 8) as u8) | 0x80), 1);
+    e.encode_uint(u64::from((s & 0xFF) as u8), 1);
+    e.encode_uint(u64::from(w[0]), 1);
+    e.encode_uint(u64::from(w[1]), 1);
+    for &b in &machine_id {
+        e.encode_uint(u64::from(b), 1);
+    }
+}
+
+fn write_96(out: &mut [u8], t: u64, s: u16, w: [u8; 2], machine_id: [u8; 3]) {
+    let mut e = Encoder::new(out);
+    e.encode_uint(t >> 2, 5);
+    e.encode_uint((((t as u16) << 14) | s) as u64, 2);
+    e.encode_uint(u64::from(w[0]), 1);
+    e.encode_uint(u64::from(w[1]), 1);
+    for &b in &machine_id {
+        e.encode_uint(u64::from(b), 1);
+    }
+}
+
+fn write_64(out: &mut [u8], t: u64, s: u16, w: u8) {
+    let mut e = Encoder::new(out);
+    e.encode_uint(t >> 2, 5);
+    e.encode_uint((((t as u16) << 14) | s) as u64, 2);
+    e.encode_uint(u64::from(w), 1);
+}
+
+/// An independent id generator.
+///
+/// Owns its worker id, machine id, epoch and timestamp/sequence state
+/// instead of relying on process-wide statics, so several generators can
+/// run side by side (e.g. one per shard, or one per test) with explicit,
+/// non-overlapping identities. Not `Sync`; share one across threads
+/// behind a `Mutex` if needed.
+pub struct Generator {
+    worker_id: u16,
+    machine_id: [u8; 4],
+    epoch: u64,
+    timestamp: Cell<u64>,
+    seq: Cell<u16>,
+}
+
+impl Generator {
+    pub fn new(worker_id: u16, machine_id: [u8; 4], epoch: u64) -> Self {
+        Generator {
+            worker_id,
+            machine_id,
+            epoch,
+            timestamp: Cell::new(0),
+            seq: Cell::new(0),
+        }
+    }
+
+    fn tick(&self, min_interval: u16) -> Result<(u64, u16), Error> {
+        let mut time = self.timestamp.get();
+        if time == 0 {
+            time = now()?;
+        }
+        let tick = next_tick(time)?;
+        let seq = if tick == time && self.seq.get() < ((1 << 14) - 1) {
+            self.seq.get() + 1
+        } else if tick == time {
+            time = wait_for_tick_after(time, min_interval)?;
+            0
+        } else {
+            time = tick;
+            0
+        };
+        self.timestamp.set(time);
+        self.seq.set(seq);
+        Ok((time, seq))
+    }
+
+    /// See `next_short_128`.
+    pub fn next_short_128(&self) -> Result<[u8; 16], Error> {
+        let (t, s) = self.tick(1)?;
+        Ok(encode_128(t, s, self.worker_id.to_be_bytes(), self.machine_id))
+    }
+
+    /// See `next_short_96`.
+    pub fn next_short_96(&self) -> Result<[u8; 12], Error> {
+        let (t, s) = self.tick(1 << TIMESTAMP42SHIFT)?;
+        let t = shift_timestamp(t, self.epoch)?;
+        let machine_id = [self.machine_id[1], self.machine_id[2], self.machine_id[3]];
+        Ok(encode_96(t, s, self.worker_id.to_be_bytes(), machine_id))
+    }
+
+    /// See `next_short_64`.
+    pub fn next_short_64(&self) -> Result<[u8; 8], Error> {
+        if self.worker_id > u16::from(u8::MAX) {
+            return Err(Error::WorkerIDOverflow);
+        }
+        let (t, s) = self.tick(10000)?;
+        let t = shift_timestamp(t, self.epoch)?;
+        Ok(encode_64(t, s, self.worker_id as u8))
+    }
+
+    /// See `uuidv1`.
+    pub fn uuidv1(&self) -> Result<[u8; 16], Error> {
+        let (t, s) = self.tick(1)?;
+        Ok(encode_128(t, s, self.worker_id.to_be_bytes(), self.machine_id))
+    }
+
+    /// Fills `out` with consecutive `next_short_128` ids.
+    ///
+    /// `out.len()` must be a multiple of 16; each 16-byte chunk is filled
+    /// in place, acquiring the timestamp/sequence state once per id
+    /// instead of allocating a fresh array per call.
+    pub fn fill_short_128(&self, out: &mut [u8]) -> Result<(), Error> {
+        if !out.len().is_multiple_of(16) {
+            return Err(Error::InvalidLength);
+        }
+        for chunk in out.chunks_mut(16) {
+            let (t, s) = self.tick(1)?;
+            write_128(chunk, t, s, self.worker_id.to_be_bytes(), self.machine_id);
+        }
+        Ok(())
+    }
+
+    /// Generates `count` consecutive `next_short_128` ids in one call.
+    pub fn next_short_128_batch(&self, count: usize) -> Result<Vec<[u8; 16]>, Error> {
+        let mut out = vec![0u8; count * 16];
+        self.fill_short_128(&mut out)?;
+        Ok(out.chunks(16).map(|c| c.try_into().unwrap()).collect())
+    }
+
+    /// Fills `out` with consecutive `next_short_96` ids. See `fill_short_128`.
+    pub fn fill_short_96(&self, out: &mut [u8]) -> Result<(), Error> {
+        if !out.len().is_multiple_of(12) {
+            return Err(Error::InvalidLength);
+        }
+        let machine_id = [self.machine_id[1], self.machine_id[2], self.machine_id[3]];
+        for chunk in out.chunks_mut(12) {
+            let (t, s) = self.tick(1 << TIMESTAMP42SHIFT)?;
+            let t = shift_timestamp(t, self.epoch)?;
+            write_96(chunk, t, s, self.worker_id.to_be_bytes(), machine_id);
+        }
+        Ok(())
+    }
+
+    /// Generates `count` consecutive `next_short_96` ids in one call.
+    pub fn next_short_96_batch(&self, count: usize) -> Result<Vec<[u8; 12]>, Error> {
+        let mut out = vec![0u8; count * 12];
+        self.fill_short_96(&mut out)?;
+        Ok(out.chunks(12).map(|c| c.try_into().unwrap()).collect())
+    }
+
+    /// Fills `out` with consecutive `next_short_64` ids. See `fill_short_128`.
+    pub fn fill_short_64(&self, out: &mut [u8]) -> Result<(), Error> {
+        if self.worker_id > u16::from(u8::MAX) {
+            return Err(Error::WorkerIDOverflow);
+        }
+        if !out.len().is_multiple_of(8) {
+            return Err(Error::InvalidLength);
+        }
+        for chunk in out.chunks_mut(8) {
+            let (t, s) = self.tick(10000)?;
+            let t = shift_timestamp(t, self.epoch)?;
+            write_64(chunk, t, s, self.worker_id as u8);
+        }
+        Ok(())
+    }
+
+    /// Generates `count` consecutive `next_short_64` ids in one call.
+    pub fn next_short_64_batch(&self, count: usize) -> Result<Vec<[u8; 8]>, Error> {
+        let mut out = vec![0u8; count * 8];
+        self.fill_short_64(&mut out)?;
+        Ok(out.chunks(8).map(|c| c.try_into().unwrap()).collect())
+    }
+}
+
+static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+fn alloc_worker_id() -> u16 {
+    let id = COUNTER.fetch_add(1, Ordering::SeqCst);
+    if id > u16::MAX as usize {
+        panic!("too many threads")
+    }
+    id as u16
+}
+
+thread_local! {
+    static DEFAULT_GENERATOR: Generator = Generator::new(alloc_worker_id(), [0, 0, 0, 0], 0);
+}
+
+static UUIDV1_GENERATOR: Mutex<Option<Generator>> = Mutex::new(None);
+
+fn with_uuidv1_generator<T>(f: impl FnOnce(&Generator) -> T) -> T {
+    let mut guard = UUIDV1_GENERATOR.lock().unwrap();
+    let generator = guard.get_or_insert_with(|| Generator::new(0, [0, 0, 0, 0], 0));
+    f(generator)
+}
+
+#[cfg(test)]
+fn timestamp() -> u64 {
+    DEFAULT_GENERATOR.with(|g| g.timestamp.get())
+}
+
+#[cfg(test)]
+fn seq() -> u16 {
+    DEFAULT_GENERATOR.with(|g| g.seq.get())
+}
+
+///
+/// for compatible UUID
+///
+/// 16 bit worker id and 32 bit machine_id
+///
+pub fn next_short_128(machine_id: [u8; 4]) -> Result<[u8; 16], Error> {
+    DEFAULT_GENERATOR.with(|g| {
+        let (t, s) = g.tick(1)?;
+        Ok(encode_128(t, s, g.worker_id.to_be_bytes(), machine_id))
+    })
+}
+
+/// Fills `out` with consecutive `next_short_128` ids sharing `machine_id`.
+///
+/// `out.len()` must be a multiple of 16. Enters the thread-local generator
+/// once per call and advances the timestamp/sequence state once per id,
+/// writing each id straight into `out` instead of returning a fresh array.
+pub fn fill_short_128(machine_id: [u8; 4], out: &mut [u8]) -> Result<(), Error> {
+    if !out.len().is_multiple_of(16) {
+        return Err(Error::InvalidLength);
+    }
+    DEFAULT_GENERATOR.with(|g| {
+        for chunk in out.chunks_mut(16) {
+            let (t, s) = g.tick(1)?;
+            write_128(chunk, t, s, g.worker_id.to_be_bytes(), machine_id);
+        }
+        Ok(())
+    })
+}
+
+/// Generates `count` consecutive `next_short_128` ids in one call.
+pub fn next_short_128_batch(machine_id: [u8; 4], count: usize) -> Result<Vec<[u8; 16]>, Error> {
+    let mut out = vec![0u8; count * 16];
+    fill_short_128(machine_id, &mut out)?;
+    Ok(out.chunks(16).map(|c| c.try_into().unwrap()).collect())
 }
 
 ///
@@ -161,30 +444,39 @@ pub fn next_short_128(machine_id: [u8; 4]) -> Result<[u8; 16], Error> {
 /// Max IDs per Second : 20_000_000
 ///
 pub fn next_short_96(machine_id: [u8; 3], epoch: u64) -> Result<[u8; 12], Error> {
-    let (mut t, s) = next(1 << TIMESTAMP42SHIFT)?;
-    t = (t
-        .checked_sub(UUID_TICKS_BETWEEN_EPOCHS)
-        .ok_or_else(|| Error::EpochException)?
-        .checked_sub(epoch)
-        .ok_or_else(|| Error::EpochException)?)
-        >> TIMESTAMP42SHIFT;
-    let t_hi = (t >> 2).to_be_bytes();
-    let [t_low_and_s_hi, s_low] = (((t as u16) << 14) | s).to_be_bytes();
-    let w = worker_id();
-    Ok([
-        t_hi[3],
-        t_hi[4],
-        t_hi[5],
-        t_hi[6],
-        t_hi[7],
-        t_low_and_s_hi,
-        s_low,
-        w[0],
-        w[1],
-        machine_id[0],
-        machine_id[1],
-        machine_id[2],
-    ])
+    DEFAULT_GENERATOR.with(|g| {
+        let (t, s) = g.tick(1 << TIMESTAMP42SHIFT)?;
+        let t = shift_timestamp(t, epoch)?;
+        Ok(encode_96(t, s, g.worker_id.to_be_bytes(), machine_id))
+    })
+}
+
+/// Fills `out` with consecutive `next_short_96` ids sharing `machine_id` and `epoch`.
+///
+/// `out.len()` must be a multiple of 12.
+pub fn fill_short_96(machine_id: [u8; 3], epoch: u64, out: &mut [u8]) -> Result<(), Error> {
+    if !out.len().is_multiple_of(12) {
+        return Err(Error::InvalidLength);
+    }
+    DEFAULT_GENERATOR.with(|g| {
+        for chunk in out.chunks_mut(12) {
+            let (t, s) = g.tick(1 << TIMESTAMP42SHIFT)?;
+            let t = shift_timestamp(t, epoch)?;
+            write_96(chunk, t, s, g.worker_id.to_be_bytes(), machine_id);
+        }
+        Ok(())
+    })
+}
+
+/// Generates `count` consecutive `next_short_96` ids in one call.
+pub fn next_short_96_batch(
+    machine_id: [u8; 3],
+    epoch: u64,
+    count: usize,
+) -> Result<Vec<[u8; 12]>, Error> {
+    let mut out = vec![0u8; count * 12];
+    fill_short_96(machine_id, epoch, &mut out)?;
+    Ok(out.chunks(12).map(|c| c.try_into().unwrap()).collect())
 }
 
 pub fn short_96_to_128(short_96: [u8; 12], epoch: u64, machine_id_hi: u8) -> [u8; 16] {
@@ -194,27 +486,7 @@ pub fn short_96_to_128(short_96: [u8; 12], epoch: u64, machine_id_hi: u8) -> [u8
         + epoch
         + UUID_TICKS_BETWEEN_EPOCHS;
     let s = u16::from_le_bytes([c[6], c[5]]) & 0x3fff;
-    let time_low = ((t & 0xFFFF_FFFF) as u32).to_be_bytes();
-    let time_mid = (((t >> 32) & 0xFFFF) as u16).to_be_bytes();
-    let time_high_and_version = ((((t >> 48) & 0x0FFF) as u16) | (1 << 12)).to_be_bytes();
-    [
-        time_low[0],
-        time_low[1],
-        time_low[2],
-        time_low[3],
-        time_mid[0],
-        time_mid[1],
-        time_high_and_version[0],
-        time_high_and_version[1],
-        (((s & 0x3F00) >> 8) as u8) | 0x80,
-        (s & 0xFF) as u8,
-        c[7],
-        c[8],
-        machine_id_hi,
-        c[9],
-        c[10],
-        c[11],
-    ]
+    encode_128(t, s, [c[7], c[8]], [machine_id_hi, c[9], c[10], c[11]])
 }
 
 ///
@@ -231,29 +503,41 @@ pub fn short_96_to_128(short_96: [u8; 12], epoch: u64, machine_id_hi: u8) -> [u8
 /// Max IDs per Second : 20_000_000
 ///
 pub fn next_short_64(epoch: u64) -> Result<[u8; 8], Error> {
-    let w = worker_id();
-    if w[0] != 0 {
-        return Err(Error::WorkerIDOverflow);
+    DEFAULT_GENERATOR.with(|g| {
+        if g.worker_id > u16::from(u8::MAX) {
+            return Err(Error::WorkerIDOverflow);
+        }
+        let (t, s) = g.tick(10000)?;
+        let t = shift_timestamp(t, epoch)?;
+        Ok(encode_64(t, s, g.worker_id as u8))
+    })
+}
+
+/// Fills `out` with consecutive `next_short_64` ids sharing `epoch`.
+///
+/// `out.len()` must be a multiple of 8.
+pub fn fill_short_64(epoch: u64, out: &mut [u8]) -> Result<(), Error> {
+    if !out.len().is_multiple_of(8) {
+        return Err(Error::InvalidLength);
     }
-    let (mut t, s) = next(10000)?;
-    t = (t
-        .checked_sub(UUID_TICKS_BETWEEN_EPOCHS)
-        .ok_or_else(|| Error::EpochException)?
-        .checked_sub(epoch)
-        .ok_or_else(|| Error::EpochException)?)
-        >> TIMESTAMP42SHIFT;
-    let t_hi = (t >> 2).to_be_bytes();
-    let [t_low_and_s_hi, s_low] = (((t as u16) << 14) | s).to_be_bytes();
-    Ok([
-        t_hi[3],
-        t_hi[4],
-        t_hi[5],
-        t_hi[6],
-        t_hi[7],
-        t_low_and_s_hi,
-        s_low,
-        w[1],
-    ])
+    DEFAULT_GENERATOR.with(|g| {
+        if g.worker_id > u16::from(u8::MAX) {
+            return Err(Error::WorkerIDOverflow);
+        }
+        for chunk in out.chunks_mut(8) {
+            let (t, s) = g.tick(10000)?;
+            let t = shift_timestamp(t, epoch)?;
+            write_64(chunk, t, s, g.worker_id as u8);
+        }
+        Ok(())
+    })
+}
+
+/// Generates `count` consecutive `next_short_64` ids in one call.
+pub fn next_short_64_batch(epoch: u64, count: usize) -> Result<Vec<[u8; 8]>, Error> {
+    let mut out = vec![0u8; count * 8];
+    fill_short_64(epoch, &mut out)?;
+    Ok(out.chunks(8).map(|c| c.try_into().unwrap()).collect())
 }
 
 pub fn short_64_to_96(short_64: [u8; 8], machine_id: [u8; 3]) -> [u8; 12] {
@@ -279,6 +563,102 @@ pub fn short_64_to_128(short_64: [u8; 8], epoch: u64, machine_id: [u8; 4]) -> [u
     short_96_to_128(short96, epoch, machine_id[0])
 }
 
+/// The fields packed into a short id, recovered by `parse_short_128`,
+/// `parse_short_96` or `parse_short_64`.
+///
+/// `timestamp` is always expressed as 100ns ticks since the UUID epoch,
+/// the same unit `next_short_*` works in internally.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ShortIdParts {
+    pub timestamp: u64,
+    pub seq: u16,
+    pub worker_id: u16,
+    pub machine_id: u32,
+    pub version: u8,
+}
+
+/// Reads big-endian unsigned integers out of a byte slice, advancing an
+/// internal offset as it goes.
+struct Decoder<'a> {
+    buf: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Decoder<'a> {
+    fn new(buf: &'a [u8]) -> Self {
+        Decoder { buf, offset: 0 }
+    }
+
+    fn decode_uint(&mut self, n_bytes: usize) -> u64 {
+        let mut value: u64 = 0;
+        for &byte in &self.buf[self.offset..self.offset + n_bytes] {
+            value = (value << 8) | u64::from(byte);
+        }
+        self.offset += n_bytes;
+        value
+    }
+}
+
+/// Reconstructs the fields packed into a `next_short_128` id.
+pub fn parse_short_128(short_128: [u8; 16]) -> ShortIdParts {
+    let mut d = Decoder::new(&short_128);
+    let time_low = d.decode_uint(4);
+    let time_mid = d.decode_uint(2);
+    let time_high_and_version = d.decode_uint(2);
+    let seq_hi = d.decode_uint(1);
+    let seq_low = d.decode_uint(1);
+    let worker_id = d.decode_uint(2) as u16;
+    let machine_id = d.decode_uint(4) as u32;
+    ShortIdParts {
+        timestamp: time_low | (time_mid << 32) | ((time_high_and_version & 0x0FFF) << 48),
+        seq: (((seq_hi & 0x3F) << 8) | seq_low) as u16,
+        worker_id,
+        machine_id,
+        version: (time_high_and_version >> 12) as u8,
+    }
+}
+
+/// Reconstructs the fields packed into a `next_short_96` id.
+///
+/// `epoch` must be the same value passed to `next_short_96` when the id
+/// was generated.
+pub fn parse_short_96(short_96: [u8; 12], epoch: u64) -> ShortIdParts {
+    let mut d = Decoder::new(&short_96);
+    let t_hi = d.decode_uint(5);
+    let t_low_and_s_hi = d.decode_uint(1);
+    let s_low = d.decode_uint(1);
+    let worker_id = d.decode_uint(2) as u16;
+    let machine_id = d.decode_uint(3) as u32;
+    let t = (t_hi << 2) | (t_low_and_s_hi >> 6);
+    ShortIdParts {
+        timestamp: (t << TIMESTAMP42SHIFT) + epoch + UUID_TICKS_BETWEEN_EPOCHS,
+        seq: (((t_low_and_s_hi & 0x3F) << 8) | s_low) as u16,
+        worker_id,
+        machine_id,
+        version: 0,
+    }
+}
+
+/// Reconstructs the fields packed into a `next_short_64` id.
+///
+/// `epoch` must be the same value passed to `next_short_64` when the id
+/// was generated.
+pub fn parse_short_64(short_64: [u8; 8], epoch: u64) -> ShortIdParts {
+    let mut d = Decoder::new(&short_64);
+    let t_hi = d.decode_uint(5);
+    let t_low_and_s_hi = d.decode_uint(1);
+    let s_low = d.decode_uint(1);
+    let worker_id = d.decode_uint(1) as u16;
+    let t = (t_hi << 2) | (t_low_and_s_hi >> 6);
+    ShortIdParts {
+        timestamp: (t << TIMESTAMP42SHIFT) + epoch + UUID_TICKS_BETWEEN_EPOCHS,
+        seq: (((t_low_and_s_hi & 0x3F) << 8) | s_low) as u16,
+        worker_id,
+        machine_id: 0,
+        version: 0,
+    }
+}
+
 #[test]
 fn test_128() {
     use uuid::Uuid;
@@ -329,57 +709,192 @@ fn test_64() {
     assert_eq!(my_uuid.get_variant().unwrap(), Variant::RFC4122);
 }
 
-use std::sync::atomic::{AtomicU16, AtomicU64};
-
-static mut TIMESTAMP_ATOM: AtomicU64 = AtomicU64::new(0);
-static mut SEQ_ATOM: AtomicU16 = AtomicU16::new(0);
+/// Crockford Base32 alphabet: no `I`, `L`, `O`, `U`, so the encoded form
+/// can't be mistaken for those letters when read aloud or transcribed.
+const BASE32_ALPHABET: &[u8; 32] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
 
-fn next_atom() -> Result<(u64, u16), Error> {
-    unsafe {
-        let seq = SEQ_ATOM.get_mut();
-        let ts = TIMESTAMP_ATOM.get_mut();
-        if *ts == 0 {
-            *ts = now()?;
+/// Encodes `bytes` MSB-first into Crockford Base32.
+///
+/// Because the ids are big-endian with the timestamp in the high bytes,
+/// this straight MSB-first grouping makes the encoded string sort in the
+/// same order as the ids themselves.
+fn base32_encode(bytes: &[u8]) -> String {
+    let total_bits = bytes.len() * 8;
+    let n_chars = total_bits.div_ceil(5);
+    let mut out = String::with_capacity(n_chars);
+    for i in 0..n_chars {
+        let bit_pos = i * 5;
+        let mut chunk: u8 = 0;
+        for b in 0..5 {
+            let bit = bit_pos + b;
+            let bit_val = if bit < total_bits {
+                (bytes[bit / 8] >> (7 - (bit % 8))) & 1
+            } else {
+                0
+            };
+            chunk = (chunk << 1) | bit_val;
         }
-        if *seq < ((1 << 14) - 1) {
-            *seq += 1;
-        } else {
-            if *ts >= now()? {
-                return Err(Error::TimeOverflow);
+        out.push(BASE32_ALPHABET[chunk as usize] as char);
+    }
+    out
+}
+
+/// Maps one Crockford Base32 character to its 5-bit value, case-insensitively
+/// and normalizing the excluded letters to their look-alike digits.
+fn base32_decode_char(c: u8) -> Option<u8> {
+    let c = match c {
+        b'i' | b'I' | b'l' | b'L' => b'1',
+        b'o' | b'O' => b'0',
+        c => c.to_ascii_uppercase(),
+    };
+    BASE32_ALPHABET.iter().position(|&a| a == c).map(|p| p as u8)
+}
+
+/// Decodes a Crockford Base32 string back into `n_bytes` bytes.
+fn base32_decode(s: &str, n_bytes: usize) -> Result<Vec<u8>, Error> {
+    let total_bits = n_bytes * 8;
+    let n_chars = total_bits.div_ceil(5);
+    if !s.is_ascii() || s.len() != n_chars {
+        return Err(Error::InvalidLength);
+    }
+    let mut out = vec![0u8; n_bytes];
+    for (i, &c) in s.as_bytes().iter().enumerate() {
+        let val = base32_decode_char(c).ok_or(Error::InvalidChar)?;
+        let bit_pos = i * 5;
+        for b in 0..5 {
+            let bit = bit_pos + b;
+            if bit >= total_bits {
+                break;
             }
-            *ts += 1;
-            *seq = 0;
-        };
-        Ok((*ts, *seq))
+            if (val >> (4 - b)) & 1 != 0 {
+                out[bit / 8] |= 1 << (7 - (bit % 8));
+            }
+        }
     }
+    Ok(out)
+}
+
+/// Encodes a `next_short_128` id as a 26-character, lexicographically
+/// sortable Crockford Base32 string.
+pub fn encode_short_128(short_128: [u8; 16]) -> String {
+    base32_encode(&short_128)
+}
+
+/// Decodes a string produced by `encode_short_128` back into its bytes.
+pub fn decode_short_128(s: &str) -> Result<[u8; 16], Error> {
+    let v = base32_decode(s, 16)?;
+    let mut out = [0u8; 16];
+    out.copy_from_slice(&v);
+    Ok(out)
+}
+
+/// Encodes a `next_short_96` id as a 20-character, lexicographically
+/// sortable Crockford Base32 string.
+pub fn encode_short_96(short_96: [u8; 12]) -> String {
+    base32_encode(&short_96)
+}
+
+/// Decodes a string produced by `encode_short_96` back into its bytes.
+pub fn decode_short_96(s: &str) -> Result<[u8; 12], Error> {
+    let v = base32_decode(s, 12)?;
+    let mut out = [0u8; 12];
+    out.copy_from_slice(&v);
+    Ok(out)
+}
+
+/// Encodes a `next_short_64` id as a 13-character, lexicographically
+/// sortable Crockford Base32 string.
+pub fn encode_short_64(short_64: [u8; 8]) -> String {
+    base32_encode(&short_64)
+}
+
+/// Decodes a string produced by `encode_short_64` back into its bytes.
+pub fn decode_short_64(s: &str) -> Result<[u8; 8], Error> {
+    let v = base32_decode(s, 8)?;
+    let mut out = [0u8; 8];
+    out.copy_from_slice(&v);
+    Ok(out)
+}
+
+#[test]
+fn test_base32_128_roundtrip() {
+    let id = next_short_128([1, 2, 3, 4]).unwrap();
+    let encoded = encode_short_128(id);
+    assert_eq!(encoded.len(), 26);
+    assert_eq!(decode_short_128(&encoded).unwrap(), id);
+    assert_eq!(decode_short_128(&encoded.to_lowercase()).unwrap(), id);
+}
+
+#[test]
+fn test_base32_96_roundtrip() {
+    let id = next_short_96([1, 2, 3], 0).unwrap();
+    let encoded = encode_short_96(id);
+    assert_eq!(encoded.len(), 20);
+    assert_eq!(decode_short_96(&encoded).unwrap(), id);
+}
+
+#[test]
+fn test_base32_64_roundtrip() {
+    let id = next_short_64(0).unwrap();
+    let encoded = encode_short_64(id);
+    assert_eq!(encoded.len(), 13);
+    assert_eq!(decode_short_64(&encoded).unwrap(), id);
+}
+
+#[test]
+fn test_base32_invalid() {
+    assert!(decode_short_128("too-short").is_err());
+    let mut bad = encode_short_128(next_short_128([1, 2, 3, 4]).unwrap());
+    bad.replace_range(0..1, "U");
+    assert!(decode_short_128(&bad).is_err());
+}
+
+#[test]
+fn test_parse_128() {
+    let id = next_short_128([1, 2, 3, 4]).unwrap();
+    let parts = parse_short_128(id);
+    assert_eq!(parts.timestamp, timestamp());
+    assert_eq!(parts.seq, seq());
+    assert_eq!(parts.machine_id, u32::from_be_bytes([1, 2, 3, 4]));
+    assert_eq!(parts.version, 1);
+}
+
+#[test]
+fn test_parse_96() {
+    let id = next_short_96([1, 2, 3], 0).unwrap();
+    let parts = parse_short_96(id, 0);
+    assert_eq!(
+        parts.timestamp,
+        timestamp() >> TIMESTAMP42SHIFT << TIMESTAMP42SHIFT
+    );
+    assert_eq!(parts.seq, seq());
+    assert_eq!(parts.machine_id, u32::from_be_bytes([0, 1, 2, 3]));
+}
+
+#[test]
+fn test_parse_64() {
+    let id = next_short_64(0).unwrap();
+    let parts = parse_short_64(id, 0);
+    assert_eq!(
+        parts.timestamp,
+        timestamp() >> TIMESTAMP42SHIFT << TIMESTAMP42SHIFT
+    );
+    assert_eq!(parts.seq, seq());
 }
 
 ///
 /// uuidv1 generator
 ///
 pub fn uuidv1(machine_id: [u8; 6]) -> Result<[u8; 16], Error> {
-    let (t, s) = next_atom()?;
-    let time_low = ((t & 0xFFFF_FFFF) as u32).to_be_bytes();
-    let time_mid = (((t >> 32) & 0xFFFF) as u16).to_be_bytes();
-    let time_high_and_version = ((((t >> 48) & 0x0FFF) as u16) | (1 << 12)).to_be_bytes();
-    Ok([
-        time_low[0],
-        time_low[1],
-        time_low[2],
-        time_low[3],
-        time_mid[0],
-        time_mid[1],
-        time_high_and_version[0],
-        time_high_and_version[1],
-        (((s & 0x3F00) >> 8) as u8) | 0x80,
-        (s & 0xFF) as u8,
-        machine_id[0],
-        machine_id[1],
-        machine_id[2],
-        machine_id[3],
-        machine_id[4],
-        machine_id[5],
-    ])
+    with_uuidv1_generator(|g| {
+        let (t, s) = g.tick(1)?;
+        Ok(encode_128(
+            t,
+            s,
+            [machine_id[0], machine_id[1]],
+            [machine_id[2], machine_id[3], machine_id[4], machine_id[5]],
+        ))
+    })
 }
 
 ///
@@ -391,12 +906,12 @@ pub fn next_short_128_sync(machine_id: [u8; 6]) -> Result<[u8; 16], Error> {
 
 #[cfg(test)]
 fn timestamp_sync() -> u64 {
-    unsafe { *TIMESTAMP_ATOM.get_mut() }
+    with_uuidv1_generator(|g| g.timestamp.get())
 }
 
 #[cfg(test)]
 fn seq_sync() -> u16 {
-    unsafe { *SEQ_ATOM.get_mut() }
+    with_uuidv1_generator(|g| g.seq.get())
 }
 
 #[test]
@@ -415,3 +930,54 @@ fn test_uuidv1() {
     assert_eq!(my_uuid.get_version_num(), 1usize);
     assert_eq!(my_uuid.get_variant().unwrap(), Variant::RFC4122);
 }
+
+#[test]
+fn test_generator_independent_workers() {
+    let a = Generator::new(1, [1, 1, 1, 1], 0);
+    let b = Generator::new(2, [2, 2, 2, 2], 0);
+    let id_a = a.next_short_128().unwrap();
+    let id_b = b.next_short_128().unwrap();
+    assert_eq!(parse_short_128(id_a).worker_id, 1);
+    assert_eq!(parse_short_128(id_b).worker_id, 2);
+}
+
+#[test]
+fn test_next_tick_clock_rollback() {
+    assert!(matches!(next_tick(u64::MAX), Err(Error::ClockRollback)));
+}
+
+#[test]
+fn test_next_short_128_batch() {
+    let ids = next_short_128_batch([1, 2, 3, 4], 8).unwrap();
+    assert_eq!(ids.len(), 8);
+    for id in &ids {
+        let parts = parse_short_128(*id);
+        assert_eq!(parts.machine_id, u32::from_be_bytes([1, 2, 3, 4]));
+        assert_eq!(parts.version, 1);
+    }
+}
+
+#[test]
+fn test_next_short_96_batch() {
+    let ids = next_short_96_batch([1, 2, 3], 0, 8).unwrap();
+    assert_eq!(ids.len(), 8);
+    for id in &ids {
+        let parts = parse_short_96(*id, 0);
+        assert_eq!(parts.machine_id, u32::from_be_bytes([0, 1, 2, 3]));
+    }
+}
+
+#[test]
+fn test_next_short_64_batch() {
+    let ids = next_short_64_batch(0, 8).unwrap();
+    assert_eq!(ids.len(), 8);
+    for id in &ids {
+        parse_short_64(*id, 0);
+    }
+}
+
+#[test]
+fn test_fill_short_128_invalid_length() {
+    let mut out = [0u8; 15];
+    assert!(fill_short_128([0, 0, 0, 0], &mut out).is_err());
+}